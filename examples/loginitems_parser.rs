@@ -1,121 +1,342 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+
 use log::LevelFilter;
 use simplelog::{Config, SimpleLogger};
-use std::env;
-use std::io::Write;
-use std::{error::Error, fs::OpenOptions};
 
-use csv;
 use macos_loginitems::loginitems::LoginItemsResults;
+use macos_loginitems::parser;
+use macos_loginitems::security_extension::SecurityExtensionToken;
 
-fn main() {
-    SimpleLogger::init(LevelFilter::Warn, Config::default())
-        .expect("Failed to initialize simple logger");
-
-    println!("Starting LoginItems parser...");
-
-    let args: Vec<String> = env::args().collect();
-    if args.len() == 2 {
-        let path = &args[1];
-        let results = macos_loginitems::parser::parse_loginitems_path(path);
-        match results {
-            Ok(data) => {
-                let mut temp_vec = Vec::new();
-                temp_vec.push(data);
-                let data_results = parse_data(temp_vec);
-                match data_results {
-                    Ok(_) => {}
-                    Err(error) => println!("Failed to output data: {:?}", error),
+/// What to parse: a single file, every user on the system, or a directory of bundled App plists
+#[derive(Debug)]
+enum Action {
+    Path(String),
+    System,
+    Bundled(String),
+}
+
+impl TryFrom<&[String]> for Action {
+    type Error = String;
+
+    fn try_from(positionals: &[String]) -> Result<Self, Self::Error> {
+        let mut positional = positionals.iter();
+        let mode = positional
+            .next()
+            .ok_or_else(|| "Missing <path|system|bundled <path>> argument".to_string())?;
+
+        match mode.as_str() {
+            "system" => Ok(Action::System),
+            "bundled" => {
+                let path = positional
+                    .next()
+                    .ok_or_else(|| "bundled requires a <path> argument".to_string())?;
+                Ok(Action::Bundled(path.to_string()))
+            }
+            path => Ok(Action::Path(path.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Json,
+    Jsonl,
+}
+
+impl TryFrom<&str> for OutputFormat {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            other => Err(format!("Unsupported --format value: {other:?}")),
+        }
+    }
+}
+
+struct Options {
+    action: Action,
+    format: OutputFormat,
+    output: String,
+    quiet: bool,
+    verbose: bool,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut format = OutputFormat::Json;
+        let mut output = String::from("-");
+        let mut quiet = false;
+        let mut verbose = false;
+        let mut positionals: Vec<String> = Vec::new();
+
+        let mut index = 0;
+        while index < args.len() {
+            match args[index].as_str() {
+                "--format" => {
+                    index += 1;
+                    let value = args.get(index).ok_or("--format requires a value")?;
+                    format = OutputFormat::try_from(value.as_str())?;
                 }
+                "--output" => {
+                    index += 1;
+                    let value = args.get(index).ok_or("--output requires a value")?;
+                    output = value.clone();
+                }
+                "--quiet" => quiet = true,
+                "--verbose" => verbose = true,
+                arg => positionals.push(arg.to_string()),
             }
-            Err(err) => println!(
-                "Failed to get loginitem data: {:?} {:?}",
-                err,
-                err.to_string()
-            ),
+            index += 1;
+        }
+
+        let action = Action::try_from(positionals.as_slice())?;
+
+        Ok(Options {
+            action,
+            format,
+            output,
+            quiet,
+            verbose,
+        })
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "Usage: loginitems_parser <path|system|bundled <path>> [--format csv|json|jsonl] [--output <file|->] [--quiet] [--verbose]"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let level = if options.quiet {
+        LevelFilter::Error
+    } else if options.verbose {
+        LevelFilter::Info
+    } else {
+        LevelFilter::Warn
+    };
+    SimpleLogger::init(level, Config::default()).expect("Failed to initialize simple logger");
+
+    if !options.quiet {
+        println!("Starting LoginItems parser...");
+    }
+
+    if options.format == OutputFormat::Jsonl {
+        // Write each record as soon as it is parsed instead of materializing the whole
+        // sweep into a Vec first, so huge multi-user collections can be streamed.
+        if let Err(err) = stream_jsonl(&options.action, &options.output) {
+            eprintln!("Failed to parse and stream loginitem data: {err}");
+            std::process::exit(1);
         }
     } else {
-        let results = macos_loginitems::parser::parse_loginitems_system();
-        match results {
-            Ok(data) => {
-                let data_results = parse_data(data);
-                match data_results {
-                    Ok(_) => {}
-                    Err(error) => println!("Failed to output data: {:?}", error),
-                }
+        let results = match &options.action {
+            Action::Path(path) => parser::parse_loginitems_path(path).map(|result| vec![result]),
+            Action::System => parser::parse_loginitems_system(),
+            Action::Bundled(path) => parser::parse_loginitems_bundled_path(path),
+        };
+
+        let results = match results {
+            Ok(results) => results,
+            Err(err) => {
+                eprintln!("Failed to get loginitem data: {err}");
+                std::process::exit(1);
             }
-            Err(err) => println!("Failed to get loginitem data: {:?}", err.to_string()),
+        };
+
+        if let Err(err) = write_output(&results, options.format, &options.output) {
+            eprintln!("Failed to output data: {err}");
+            std::process::exit(1);
         }
     }
+
+    if !options.quiet {
+        println!("\nFinished parsing LoginItems data.");
+    }
 }
 
-fn parse_data(results: Vec<LoginItemsResults>) -> Result<(), Box<dyn Error>> {
-    let mut writer = csv::Writer::from_path("output.csv")?;
-    let mut json_file = OpenOptions::new()
-        .append(true)
+fn writer_for(output: &str) -> Result<Box<dyn Write>, io::Error> {
+    if output == "-" {
+        return Ok(Box::new(io::stdout()));
+    }
+    let file = OpenOptions::new()
         .create(true)
-        .open("output.json")?;
+        .truncate(true)
+        .write(true)
+        .open(output)?;
+    Ok(Box::new(file))
+}
+
+fn write_output(
+    results: &[LoginItemsResults],
+    format: OutputFormat,
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Csv => write_csv(results, output),
+        OutputFormat::Json => write_json(results, output),
+        OutputFormat::Jsonl => unreachable!("Jsonl output is produced by stream_jsonl instead"),
+    }
+}
 
-    writer.write_record(&[
+fn write_json(results: &[LoginItemsResults], output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = writer_for(output)?;
+    let serialized = serde_json::to_string(results)?;
+    writer.write_all(serialized.as_bytes())?;
+    Ok(())
+}
+
+/// Parse `action` and write one `LoginItemsResults` record per line as it comes out of the
+/// parser, instead of collecting the whole sweep into a `Vec` first. This is what lets JSONL
+/// mode stream a huge multi-user collection without holding all of it in memory at once.
+fn stream_jsonl(action: &Action, output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = writer_for(output)?;
+    let mut write_err: Option<Box<dyn std::error::Error>> = None;
+
+    let mut write_line = |result: LoginItemsResults| {
+        if write_err.is_some() {
+            return;
+        }
+        let outcome = serde_json::to_string(&result)
+            .map_err(Box::<dyn std::error::Error>::from)
+            .and_then(|line| {
+                writer.write_all(line.as_bytes())?;
+                writer.write_all(b"\n")?;
+                Ok(())
+            });
+        if let Err(err) = outcome {
+            write_err = Some(err);
+        }
+    };
+
+    match action {
+        Action::Path(path) => write_line(parser::parse_loginitems_path(path)?),
+        Action::System => parser::parse_loginitems_system_with(write_line)?,
+        Action::Bundled(path) => parser::parse_loginitems_bundled_path_with(path, write_line)?,
+    }
+
+    match write_err {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+fn security_extension_class(token: &Option<SecurityExtensionToken>) -> String {
+    token
+        .as_ref()
+        .map(|token| format!("{:?}", token.class))
+        .unwrap_or_default()
+}
+
+fn security_extension_target_path(token: &Option<SecurityExtensionToken>) -> String {
+    token
+        .as_ref()
+        .map(|token| token.target_path.clone())
+        .unwrap_or_default()
+}
+
+fn write_csv(results: &[LoginItemsResults], output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(writer_for(output)?);
+
+    writer.write_record([
         "Path",
         "CNID Path",
         "Target Creation Timestamp",
+        "Target Creation Timestamp ISO",
         "Volume Path",
         "Volume URL",
         "Volume Name",
         "Volume UUID",
         "Volume Size",
         "Volume Creation",
+        "Volume Creation ISO",
         "Volume Flags",
+        "Volume Flags Named",
         "Volume Root",
         "Localized Name",
         "Security Extension RW",
         "Security Extension RO",
+        "Security Extension RW Class",
+        "Security Extension RW Target Path",
+        "Security Extension RO Class",
+        "Security Extension RO Target Path",
         "Target Flags",
+        "Target Flags Named",
         "Creator Username",
         "Creator UID",
         "Folder Index",
         "Creation Options",
+        "Creation Options Named",
         "Is App Bundled",
         "APP ID",
         "APP Binary",
         "Path Created",
+        "Path Created ISO",
         "Path Modified",
+        "Path Modified ISO",
         "Path Accessed",
+        "Path Accessed ISO",
         "Path Changed",
+        "Path Changed ISO",
         "Has Executable Flag",
         "Has File Reference Flag",
         "Source",
     ])?;
 
-    for result in &results {
+    for result in results {
         for loginitem in &result.results {
-            writer.write_record(&[
+            writer.write_record([
                 loginitem.path.join("/"),
                 format!("{:?}", loginitem.cnid_path),
                 loginitem.creation.to_string(),
+                loginitem.creation_iso.to_owned(),
                 loginitem.volume_path.to_string(),
                 loginitem.volume_url.to_owned(),
                 loginitem.volume_name.to_owned(),
                 loginitem.volume_uuid.to_owned(),
                 loginitem.volume_size.to_string(),
                 loginitem.volume_creation.to_string(),
+                loginitem.volume_creation_iso.to_owned(),
                 format!("{:?}", loginitem.volume_flag),
+                format!("{:?}", loginitem.volume_flag_named),
                 loginitem.volume_root.to_string(),
                 loginitem.localized_name.to_owned(),
                 loginitem.security_extension_rw.to_owned(),
                 loginitem.security_extension_ro.to_owned(),
+                security_extension_class(&loginitem.security_extension_rw_decoded),
+                security_extension_target_path(&loginitem.security_extension_rw_decoded),
+                security_extension_class(&loginitem.security_extension_ro_decoded),
+                security_extension_target_path(&loginitem.security_extension_ro_decoded),
                 format!("{:?}", loginitem.target_flags),
+                format!("{:?}", loginitem.target_flags_named),
                 loginitem.username.to_owned(),
                 loginitem.uid.to_string(),
                 loginitem.folder_index.to_string(),
                 loginitem.creation_options.to_string(),
+                format!("{:?}", loginitem.creation_options_named),
                 loginitem.is_bundled.to_string(),
                 loginitem.app_id.to_owned(),
                 loginitem.app_binary.to_owned(),
                 loginitem.created_time.to_string(),
+                loginitem.created_time_iso.to_owned(),
                 loginitem.modified_time.to_string(),
+                loginitem.modified_time_iso.to_owned(),
                 loginitem.accessed_time.to_string(),
+                loginitem.accessed_time_iso.to_owned(),
                 loginitem.changed_time.to_string(),
+                loginitem.changed_time_iso.to_owned(),
                 loginitem.has_executable_flag.to_string(),
                 loginitem.file_ref_flag.to_string(),
                 result.path.to_string(),
@@ -124,9 +345,5 @@ fn parse_data(results: Vec<LoginItemsResults>) -> Result<(), Box<dyn Error>> {
     }
 
     writer.flush()?;
-    let serde_data = serde_json::to_string(&results)?;
-    json_file.write_all(serde_data.as_bytes())?;
-    println!("\nFinished parsing LoginItems data. Saved results to: output.csv and output.json");
-
     Ok(())
 }
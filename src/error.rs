@@ -2,19 +2,52 @@ use std::fmt;
 
 #[derive(Debug)]
 pub enum LoginItemError {
-    Path,
-    Plist,
-    Bookmark,
+    Path {
+        path: String,
+        source: std::io::Error,
+    },
+    Plist {
+        path: String,
+        source: plist::Error,
+    },
+    Bookmark {
+        path: String,
+        /// 0-based position of the failing bookmark blob among the blobs extracted from the
+        /// PLIST at `path`, in parse order. The blobs are separate, non-contiguous `Data`
+        /// values inside the plist, not a real byte stream, so this is an entry index rather
+        /// than a seekable byte offset into any file.
+        entry_index: usize,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
 
-impl std::error::Error for LoginItemError {}
+impl std::error::Error for LoginItemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoginItemError::Path { source, .. } => Some(source),
+            LoginItemError::Plist { source, .. } => Some(source),
+            LoginItemError::Bookmark { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
 
 impl fmt::Display for LoginItemError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            LoginItemError::Path => write!(f, "Failed to get provided path"),
-            LoginItemError::Plist => write!(f, "No bookmark data"),
-            LoginItemError::Bookmark => write!(f, "Could not parse bookmark data"),
+            LoginItemError::Path { path, source } => {
+                write!(f, "Failed to get provided path {path:?}: {source}")
+            }
+            LoginItemError::Plist { path, source } => {
+                write!(f, "No bookmark data in {path:?}: {source}")
+            }
+            LoginItemError::Bookmark {
+                path,
+                entry_index,
+                source,
+            } => write!(
+                f,
+                "Could not parse bookmark entry #{entry_index} in {path:?}: {source}"
+            ),
         }
     }
 }
@@ -0,0 +1,144 @@
+//! Named bitflag decoders for the raw integers embedded in LoginItems bookmark data
+//!
+//! `LoginItemsData` exposes `volume_flag`, `target_flags`, and `creation_options` as raw
+//! numbers straight out of the bookmark. These helpers translate them into the named
+//! constants Apple defines for `CFURLResourcePropertyFlags`/`CFURLVolumePropertyFlags` and
+//! `NSURLBookmarkCreationOptions` so the values are usable without a separate lookup table.
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// Resource property flags describing the bookmark target (file/directory/volume/etc)
+    pub struct TargetFlags: u64 {
+        const IS_REGULAR_FILE = 0x0000_0001;
+        const IS_DIRECTORY = 0x0000_0002;
+        const IS_SYMLINK = 0x0000_0004;
+        const IS_VOLUME = 0x0000_0008;
+        const IS_PACKAGE = 0x0000_0010;
+        const IS_SYSTEM_IMMUTABLE = 0x0000_0020;
+        const IS_USER_IMMUTABLE = 0x0000_0040;
+        const IS_HIDDEN = 0x0000_0080;
+        const HAS_HIDDEN_EXTENSION = 0x0000_0100;
+        const IS_APPLICATION = 0x0000_0200;
+        const IS_COMPRESSED = 0x0000_0400;
+        const CAN_SET_HIDDEN_EXTENSION = 0x0000_0800;
+        const IS_READABLE = 0x0000_1000;
+        const IS_WRITABLE = 0x0000_2000;
+        const IS_EXECUTABLE = 0x0000_4000;
+        const IS_ALIAS_FILE = 0x0000_8000;
+        const IS_MOUNT_TRIGGER = 0x0001_0000;
+    }
+}
+
+bitflags! {
+    /// Volume property flags describing the volume a bookmark target lives on
+    pub struct VolumeFlags: u64 {
+        const IS_LOCAL = 0x0000_0001;
+        const IS_AUTOMOUNTED = 0x0000_0002;
+        const SUPPORTS_PERSISTENT_IDS = 0x0000_0004;
+        const SUPPORTS_SYMBOLIC_LINKS = 0x0000_0008;
+        const SUPPORTS_HARD_LINKS = 0x0000_0020;
+        const SUPPORTS_JOURNALING = 0x0000_0040;
+        const IS_JOURNALING = 0x0000_0080;
+        const SUPPORTS_SPARSE_FILES = 0x0000_0100;
+        const SUPPORTS_ZERO_RUNS = 0x0000_0200;
+        const SUPPORTS_CASE_SENSITIVE_NAMES = 0x0000_1000;
+        const SUPPORTS_CASE_PRESERVED_NAMES = 0x0001_0000;
+        const SUPPORTS_ROOT_DIRECTORY_DATES = 0x0000_0800;
+        const IS_ROOT_FILESYSTEM = 0x1_0000_0000;
+    }
+}
+
+bitflags! {
+    /// `NSURLBookmarkCreationOptions` used when the bookmark was created
+    pub struct CreationFlags: i32 {
+        const PREFER_FILE_ID_RESOLUTION = 1 << 8;
+        const MINIMAL_BOOKMARK = 1 << 9;
+        const SUITABLE_FOR_BOOKMARK_FILE = 1 << 10;
+        const WITH_SECURITY_SCOPE = 1 << 11;
+        const SECURITY_SCOPE_ALLOW_ONLY_READ_ACCESS = 1 << 12;
+        const WITHOUT_IMPLICIT_SECURITY_SCOPE = 1 << 29;
+    }
+}
+
+/// Decode a raw target flags integer into its named `TargetFlags` members plus any
+/// unrecognized bits (reported as `unknown_0x...` so no information is silently dropped)
+pub fn decode_target_flags(raw: u64) -> Vec<String> {
+    decode_named_flags(TargetFlags::from_bits_truncate(raw), raw)
+}
+
+/// Decode a raw volume flags integer into its named `VolumeFlags` members
+pub fn decode_volume_flags(raw: u64) -> Vec<String> {
+    decode_named_flags(VolumeFlags::from_bits_truncate(raw), raw)
+}
+
+/// Decode the raw bookmark creation options integer into its named `CreationFlags` members
+pub fn decode_creation_flags(raw: i32) -> Vec<String> {
+    let flags = CreationFlags::from_bits_truncate(raw);
+    let mut names: Vec<String> = flags
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+
+    let unknown = raw & !flags.bits();
+    if unknown != 0 {
+        names.push(format!("unknown_0x{:x}", unknown));
+    }
+    names
+}
+
+fn decode_named_flags<T>(flags: T, raw: u64) -> Vec<String>
+where
+    T: bitflags::Flags<Bits = u64>,
+{
+    let mut names: Vec<String> = flags
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+
+    let unknown = raw & !flags.bits();
+    if unknown != 0 {
+        names.push(format!("unknown_0x{:x}", unknown));
+    }
+    names
+}
+
+/// Decode every raw value in a `volume_flag`/`target_flags` array, preserving ordering
+pub fn decode_flag_array(raw: &[u64], decode_one: fn(u64) -> Vec<String>) -> Vec<Vec<String>> {
+    raw.iter().map(|value| decode_one(*value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_creation_flags, decode_target_flags, decode_volume_flags};
+
+    #[test]
+    fn test_decode_target_flags() {
+        let names = decode_target_flags(2);
+        assert_eq!(names, vec!["is_directory"]);
+    }
+
+    #[test]
+    fn test_decode_target_flags_package_app() {
+        let names = decode_target_flags(530);
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&String::from("is_directory")));
+        assert!(names.contains(&String::from("is_package")));
+        assert!(names.contains(&String::from("is_application")));
+    }
+
+    #[test]
+    fn test_decode_volume_flags_unknown_bits() {
+        // Real fixture value (`is_local` | `is_root_filesystem`) OR'd with 0x10, a gap bit
+        // that isn't assigned to any `VolumeFlags` member
+        let names = decode_volume_flags(4294967425 | 0x10);
+        assert!(names.contains(&String::from("is_local")));
+        assert!(names.iter().any(|name| name.starts_with("unknown_0x")));
+    }
+
+    #[test]
+    fn test_decode_creation_flags() {
+        let names = decode_creation_flags(536870912);
+        assert_eq!(names, vec!["without_implicit_security_scope"]);
+    }
+}
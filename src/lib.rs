@@ -0,0 +1,12 @@
+//! Parse macOS LoginItems data
+//!
+//! Provides a library to parse LoginItems data from `com.apple.backgrounditems.btm`
+//! and `com.apple.LSSharedFileList.*.sfl2` files.
+
+pub mod error;
+pub mod flags;
+pub mod loginitems;
+pub mod loginitems_plist;
+pub mod parser;
+pub mod security_extension;
+pub mod watch;
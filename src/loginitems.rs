@@ -4,48 +4,85 @@
 
 use log::{error, info, warn};
 use std::fs::{self, Metadata};
-use std::io::{Error, ErrorKind};
+use std::io::{Error, ErrorKind, Read};
 use std::{fs::read_dir, os::macos::fs::MetadataExt, path::Path};
 
 use serde::Serialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
 use crate::error::LoginItemError;
+use crate::flags::{decode_creation_flags, decode_flag_array, decode_target_flags, decode_volume_flags};
 use crate::loginitems_plist;
+use crate::security_extension::{classify_raw_token, parse_security_extension_token, SecurityExtensionToken};
+
+/// Seconds between the Unix epoch (1970-01-01) and the Core Foundation absolute time
+/// epoch (2001-01-01), used to normalize bookmark timestamps to Unix time
+const CF_ABSOLUTE_EPOCH_OFFSET: i64 = 978_307_200;
+
+/// Convert a Core Foundation absolute time (seconds since 2001-01-01 00:00:00 UTC) to Unix time
+fn cf_absolute_to_unix(cf_seconds: f64) -> i64 {
+    cf_seconds as i64 + CF_ABSOLUTE_EPOCH_OFFSET
+}
+
+/// Format a Unix timestamp as an RFC3339/ISO8601 string, or an empty string if it is out of range
+fn unix_to_iso(unix_seconds: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(unix_seconds) {
+        Ok(datetime) => datetime.format(&Rfc3339).unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
 
 #[derive(Debug, Serialize)]
 pub struct LoginItemsResults {
     pub results: Vec<LoginItemsData>,
     pub path: String,
+    pub username: String,  // Owning username, populated when parsed via `parse_all_users`
+    pub home_path: String, // Owning user's home directory, populated via `parse_all_users`
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LoginItemsData {
     pub path: Vec<String>,          // Path to binary to run
     pub cnid_path: Vec<i64>,        // Path represented as Catalog Node ID
-    pub creation: f64,              // Created timestamp of binary target
+    pub creation: f64,              // Created timestamp of binary target, CF absolute time (raw)
+    pub creation_unix: i64,         // `creation` normalized to Unix seconds
+    pub creation_iso: String,       // `creation_unix` formatted as RFC3339
     pub volume_path: String,        // Root
     pub volume_url: String,         // URL type
     pub volume_name: String,        // Name of Volume
     pub volume_uuid: String,        // Volume UUID string
     pub volume_size: i64,           // Size of Volume
-    pub volume_creation: f64,       // Created timestamp of Volume
+    pub volume_creation: f64,       // Created timestamp of Volume, CF absolute time (raw)
+    pub volume_creation_unix: i64,  // `volume_creation` normalized to Unix seconds
+    pub volume_creation_iso: String, // `volume_creation_unix` formatted as RFC3339
     pub volume_flag: Vec<u64>,      // Volume Property flags
+    pub volume_flag_named: Vec<Vec<String>>, // Volume Property flags decoded into named sets
     pub volume_root: bool,          // If Volume is filesystem root
     pub localized_name: String,     // Optional localized name of target binary
-    pub security_extension: String, // Optional Security extension of target binary
+    pub security_extension_rw: String, // Raw read-write sandbox security extension token, if any
+    pub security_extension_ro: String, // Raw read-only sandbox security extension token, if any
+    pub security_extension_rw_decoded: Option<SecurityExtensionToken>, // `security_extension_rw` split into structured fields
+    pub security_extension_ro_decoded: Option<SecurityExtensionToken>, // `security_extension_ro` split into structured fields
+    pub file_ref_flag: bool,        // Whether the bookmark target is a file reference
     pub target_flags: Vec<u64>,     // Resource property flags
+    pub target_flags_named: Vec<Vec<String>>, // Resource property flags decoded into named sets
     pub username: String,           // Username related to bookmark
     pub folder_index: i64,          // Folder index number
     pub uid: i32,                   // User UID
     pub creation_options: i32,      // Bookmark creation options
+    pub creation_options_named: Vec<String>, // Bookmark creation options decoded into named flags
     pub is_bundled: bool,           // Is loginitem in App
     pub app_id: String,             // App ID
     pub app_binary: String,         // App binary
     pub has_executable_flag: bool,  // Can loginitem be executed
     pub created_time: i64,
+    pub created_time_iso: String,
     pub modified_time: i64,
+    pub modified_time_iso: String,
     pub accessed_time: i64,
+    pub accessed_time_iso: String,
     pub changed_time: i64,
+    pub changed_time_iso: String,
 }
 
 impl LoginItemsData {
@@ -58,14 +95,58 @@ impl LoginItemsData {
             Ok(data) => data,
             Err(err) => {
                 error!("Failed to read loginitem PLIST file {:?}: {:?}", path, err);
-                return Err(LoginItemError::Plist);
+                return Err(LoginItemError::Plist {
+                    path: path.to_string(),
+                    source: err,
+                });
+            }
+        };
+
+        let mut loginitems_results =
+            LoginItemsData::bookmarks_to_results(loginitems_data, path)?;
+        loginitems_results.path = path.to_string();
+
+        Ok(loginitems_results)
+    }
+
+    /// Parse User LoginItems from an in-memory buffer (e.g. a `.btm`/`.sfl2` blob carved out of
+    /// a disk image, memory, or piped stdin) rather than an on-disk file
+    pub fn parse_loginitems_bytes(data: &[u8]) -> Result<LoginItemsResults, LoginItemError> {
+        LoginItemsData::parse_loginitems_reader(data)
+    }
+
+    /// Parse User LoginItems from any `Read` source, running the same plist + bookmark decode
+    /// pipeline as [`LoginItemsData::parse_loginitems`]
+    pub fn parse_loginitems_reader<R: Read>(reader: R) -> Result<LoginItemsResults, LoginItemError> {
+        let loginitems_results = loginitems_plist::get_bookmarks_from_reader(reader);
+
+        let loginitems_data = match loginitems_results {
+            Ok(data) => data,
+            Err(err) => {
+                error!("Failed to read loginitem PLIST data: {:?}", err);
+                return Err(LoginItemError::Plist {
+                    path: String::from("<bytes>"),
+                    source: err,
+                });
             }
         };
+
+        LoginItemsData::bookmarks_to_results(loginitems_data, "<bytes>")
+    }
+
+    /// Parse bookmark blobs into `LoginItemsData` entries, resolving target timestamps along
+    /// the way. The caller is responsible for setting `path` on the returned `LoginItemsResults`
+    fn bookmarks_to_results(
+        loginitems_data: Vec<Vec<u8>>,
+        source_path: &str,
+    ) -> Result<LoginItemsResults, LoginItemError> {
         if loginitems_data.is_empty() {
             info!("No loginitems found");
             let loginitems_empty = LoginItemsResults {
                 results: Vec::new(),
                 path: String::new(),
+                username: String::new(),
+                home_path: String::new(),
             };
             return Ok(loginitems_empty);
         }
@@ -73,42 +154,67 @@ impl LoginItemsData {
         let mut loginitems_results = LoginItemsResults {
             results: Vec::new(),
             path: String::new(),
+            username: String::new(),
+            home_path: String::new(),
         };
-        for data in loginitems_data {
+        for (entry_index, data) in loginitems_data.into_iter().enumerate() {
             let results = macos_bookmarks::parser::parse_bookmark(&data);
             let bookmark = match results {
                 Ok(bookmark_data) => bookmark_data,
                 Err(err) => {
                     error!("Failed to parse bookmark data: {:?}", err);
-                    return Err(LoginItemError::Bookmark);
+                    return Err(LoginItemError::Bookmark {
+                        path: source_path.to_string(),
+                        entry_index,
+                        source: Box::new(Error::new(ErrorKind::InvalidData, format!("{err:?}"))),
+                    });
                 }
             };
+            let creation_unix = cf_absolute_to_unix(bookmark.creation);
+            let volume_creation_unix = cf_absolute_to_unix(bookmark.volume_creation);
+            let (security_extension_rw, security_extension_ro) =
+                classify_raw_token(&bookmark.security_extension);
             let mut loginitem_data = LoginItemsData {
                 path: bookmark.path,
                 cnid_path: bookmark.cnid_path,
                 creation: bookmark.creation,
+                creation_unix,
+                creation_iso: unix_to_iso(creation_unix),
                 volume_path: bookmark.volume_path,
                 volume_url: bookmark.volume_url,
                 volume_name: bookmark.volume_name,
                 volume_uuid: bookmark.volume_uuid,
                 volume_size: bookmark.volume_size,
                 volume_creation: bookmark.volume_creation,
+                volume_creation_unix,
+                volume_creation_iso: unix_to_iso(volume_creation_unix),
+                volume_flag_named: decode_flag_array(&bookmark.volume_flag, decode_volume_flags),
                 volume_flag: bookmark.volume_flag,
                 volume_root: bookmark.volume_root,
                 localized_name: bookmark.localized_name,
-                security_extension: bookmark.security_extension,
+                security_extension_rw_decoded: parse_security_extension_token(&security_extension_rw),
+                security_extension_ro_decoded: parse_security_extension_token(&security_extension_ro),
+                security_extension_rw,
+                security_extension_ro,
+                file_ref_flag: bookmark.file_ref_flag,
+                target_flags_named: decode_flag_array(&bookmark.target_flags, decode_target_flags),
                 target_flags: bookmark.target_flags,
                 username: bookmark.username,
                 folder_index: bookmark.folder_index,
                 uid: bookmark.uid,
+                creation_options_named: decode_creation_flags(bookmark.creation_options),
                 creation_options: bookmark.creation_options,
                 is_bundled: false,
                 app_id: String::new(),
                 app_binary: String::new(),
                 created_time: 0,
+                created_time_iso: String::new(),
                 modified_time: 0,
+                modified_time_iso: String::new(),
                 accessed_time: 0,
+                accessed_time_iso: String::new(),
                 changed_time: 0,
+                changed_time_iso: String::new(),
                 has_executable_flag: bookmark.is_executable,
             };
             let app_path = format!("/{}", loginitem_data.path.join("/"));
@@ -116,9 +222,13 @@ impl LoginItemsData {
             match metadata_results {
                 Ok(metadata) => {
                     loginitem_data.created_time = metadata.st_birthtime();
+                    loginitem_data.created_time_iso = unix_to_iso(loginitem_data.created_time);
                     loginitem_data.modified_time = metadata.st_mtime();
+                    loginitem_data.modified_time_iso = unix_to_iso(loginitem_data.modified_time);
                     loginitem_data.accessed_time = metadata.st_atime();
+                    loginitem_data.accessed_time_iso = unix_to_iso(loginitem_data.accessed_time);
                     loginitem_data.changed_time = metadata.st_ctime();
+                    loginitem_data.changed_time_iso = unix_to_iso(loginitem_data.changed_time);
                 }
                 Err(err) => error!(
                     "Failed to get timestamps associated with loginitem {:?}: {:?}",
@@ -128,22 +238,128 @@ impl LoginItemsData {
 
             loginitems_results.results.push(loginitem_data);
         }
-        loginitems_results.path = path.to_string();
 
         Ok(loginitems_results)
     }
 
+    /// Walk every user home directory under `/Users/` and parse the `backgrounditems.btm` and
+    /// any `com.apple.sharedfilelist` SFL/SFL2 files found there, tagging each result with the
+    /// owning username and home path. Unreadable or missing paths are skipped with a warning
+    /// rather than aborting the whole sweep.
+    pub fn parse_all_users() -> Result<Vec<LoginItemsResults>, LoginItemError> {
+        let mut all_results: Vec<LoginItemsResults> = Vec::new();
+        LoginItemsData::parse_all_users_with(|parsed| all_results.push(parsed))?;
+        Ok(all_results)
+    }
+
+    /// Same sweep as [`LoginItemsData::parse_all_users`], but invokes `callback` with each
+    /// user's results as soon as they are parsed instead of collecting them into a `Vec`
+    /// first, so a caller can stream the results of a multi-user sweep without holding all
+    /// of them in memory at once
+    pub fn parse_all_users_with(
+        mut callback: impl FnMut(LoginItemsResults),
+    ) -> Result<(), LoginItemError> {
+        let base_directory = "/Users/";
+
+        let dir_results = read_dir(base_directory);
+        let read_dir = match dir_results {
+            Ok(dir) => dir,
+            Err(err) => {
+                error!("Failed to read base User directory: {:?}", err);
+                return Err(LoginItemError::Path {
+                    path: base_directory.to_string(),
+                    source: err,
+                });
+            }
+        };
+
+        for dir in read_dir {
+            let entry = match dir {
+                Ok(results) => results,
+                Err(err) => {
+                    warn!("Could not get file entry in base User directory: {:?}", err);
+                    continue;
+                }
+            };
+
+            if !entry.path().is_dir() {
+                continue;
+            }
+
+            let username = entry.file_name().to_string_lossy().to_string();
+            let home_path = entry.path().display().to_string();
+
+            for loginitems_path in LoginItemsData::user_loginitems_paths(&home_path) {
+                let results = LoginItemsData::parse_loginitems(&loginitems_path);
+                match results {
+                    Ok(mut parsed) => {
+                        parsed.username = username.clone();
+                        parsed.home_path = home_path.clone();
+                        callback(parsed);
+                    }
+                    Err(err) => warn!(
+                        "Failed to parse LoginItems for user {} at {:?}: {:?}",
+                        username, loginitems_path, err
+                    ),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collect the `backgrounditems.btm` and any `*.sfl`/`*.sfl2` LoginItems files that exist
+    /// under a single user's home directory
+    pub(crate) fn user_loginitems_paths(home_path: &str) -> Vec<String> {
+        let mut paths = Vec::new();
+
+        let btm_path = format!(
+            "{home_path}/Library/Application Support/com.apple.backgroundtaskmanagementagent/backgrounditems.btm"
+        );
+        if Path::new(&btm_path).is_file() {
+            paths.push(btm_path);
+        }
+
+        let sfl_directory = format!("{home_path}/Library/Application Support/com.apple.sharedfilelist");
+        if let Ok(dir) = read_dir(&sfl_directory) {
+            for entry in dir.flatten() {
+                let entry_path = entry.path().display().to_string();
+                if entry_path.ends_with(".sfl") || entry_path.ends_with(".sfl2") {
+                    paths.push(entry_path);
+                }
+            }
+        }
+
+        paths
+    }
+
     pub fn loginitems_bundled_apps_path(
         path: &str,
     ) -> Result<Vec<LoginItemsResults>, LoginItemError> {
         let mut loginitems_vec: Vec<LoginItemsResults> = Vec::new();
+        LoginItemsData::loginitems_bundled_apps_path_with(path, |loginitems| {
+            loginitems_vec.push(loginitems)
+        })?;
+        Ok(loginitems_vec)
+    }
 
+    /// Same sweep as [`LoginItemsData::loginitems_bundled_apps_path`], but invokes `callback`
+    /// with each bundled App's results as soon as they are parsed instead of collecting them
+    /// into a `Vec` first, so a caller can stream the results without holding all of them in
+    /// memory at once
+    pub fn loginitems_bundled_apps_path_with(
+        path: &str,
+        mut callback: impl FnMut(LoginItemsResults),
+    ) -> Result<(), LoginItemError> {
         let dir_results = read_dir(path);
         let read_dir = match dir_results {
             Ok(dir) => dir,
             Err(err) => {
                 error!("Failed to read LoginItem bundled App directory: {:?}", err);
-                return Err(LoginItemError::Path);
+                return Err(LoginItemError::Path {
+                    path: path.to_string(),
+                    source: err,
+                });
             }
         };
 
@@ -151,6 +367,8 @@ impl LoginItemsData {
             let mut loginitems = LoginItemsResults {
                 results: Vec::new(),
                 path: String::new(),
+                username: String::new(),
+                home_path: String::new(),
             };
 
             let entry_result = dir;
@@ -179,28 +397,43 @@ impl LoginItemsData {
                             path: Vec::new(),
                             cnid_path: Vec::new(),
                             creation: 0.0,
+                            creation_unix: CF_ABSOLUTE_EPOCH_OFFSET,
+                            creation_iso: unix_to_iso(CF_ABSOLUTE_EPOCH_OFFSET),
                             volume_path: String::new(),
                             volume_url: String::new(),
                             volume_name: String::new(),
                             volume_uuid: String::new(),
                             volume_size: 0,
                             volume_creation: 0.0,
+                            volume_creation_unix: CF_ABSOLUTE_EPOCH_OFFSET,
+                            volume_creation_iso: unix_to_iso(CF_ABSOLUTE_EPOCH_OFFSET),
                             volume_flag: Vec::new(),
+                            volume_flag_named: Vec::new(),
                             volume_root: false,
                             localized_name: String::new(),
-                            security_extension: String::new(),
+                            security_extension_rw: String::new(),
+                            security_extension_ro: String::new(),
+                            security_extension_rw_decoded: None,
+                            security_extension_ro_decoded: None,
+                            file_ref_flag: false,
                             target_flags: Vec::new(),
+                            target_flags_named: Vec::new(),
                             username: String::new(),
                             folder_index: 0,
                             uid: 0,
                             creation_options: 0,
+                            creation_options_named: Vec::new(),
                             is_bundled: true,
                             app_id: String::new(),
                             app_binary: String::new(),
                             created_time: 0,
+                            created_time_iso: String::new(),
                             modified_time: 0,
+                            modified_time_iso: String::new(),
                             accessed_time: 0,
+                            accessed_time_iso: String::new(),
                             changed_time: 0,
+                            changed_time_iso: String::new(),
                             has_executable_flag: false,
                         };
 
@@ -227,10 +460,10 @@ impl LoginItemsData {
                     );
                 }
             }
-            loginitems_vec.push(loginitems);
+            callback(loginitems);
         }
 
-        Ok(loginitems_vec)
+        Ok(())
     }
 
     /// Get loginitem data from embedded loginitems in Apps
@@ -239,6 +472,15 @@ impl LoginItemsData {
         LoginItemsData::loginitems_bundled_apps_path(default_path)
     }
 
+    /// Same sweep as [`LoginItemsData::loginitem_apps_system`], but streams each result to
+    /// `callback` instead of collecting into a `Vec` first
+    pub fn loginitem_apps_system_with(
+        callback: impl FnMut(LoginItemsResults),
+    ) -> Result<(), LoginItemError> {
+        let default_path = "/var/db/com.apple.xpc.launchd/";
+        LoginItemsData::loginitems_bundled_apps_path_with(default_path, callback)
+    }
+
     fn timestamps(path: &str) -> Result<Metadata, std::io::Error> {
         if !Path::exists(Path::new(path)) {
             return Err(Error::new(ErrorKind::InvalidInput, "path not found"));
@@ -261,6 +503,14 @@ mod tests {
         assert!(data.len() >= 1);
     }
 
+    #[test]
+    #[ignore = "User accounts and LoginItems vary on a live system"]
+    fn test_parse_all_users() {
+        let data = LoginItemsData::parse_all_users().unwrap();
+        assert!(data.len() >= 1);
+        assert!(!data[0].username.is_empty());
+    }
+
     #[test]
     fn test_loginitems_bundled_apps_path() {
         let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -302,10 +552,21 @@ mod tests {
         assert_eq!(data.results[0].volume_size, 160851517440);
         assert_eq!(data.results[0].volume_creation, 241134516.0);
         assert_eq!(data.results[0].volume_flag, [4294967425, 4294972399, 0]);
+        assert!(data.results[0].volume_flag_named[0].contains(&"is_local".to_string()));
         assert_eq!(data.results[0].volume_root, true);
         assert_eq!(data.results[0].localized_name, "Syncthing");
-        assert_eq!(data.results[0].security_extension, "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29;00000000;00000000;0000000000000020;com.apple.app-sandbox.read-write;01;01000004;00000000000ac62a;/applications/syncthing.app\0");
+        assert_eq!(data.results[0].security_extension_rw, "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29;00000000;00000000;0000000000000020;com.apple.app-sandbox.read-write;01;01000004;00000000000ac62a;/applications/syncthing.app\0");
+        assert_eq!(data.results[0].security_extension_ro, "");
+        assert_eq!(
+            data.results[0]
+                .security_extension_rw_decoded
+                .as_ref()
+                .unwrap()
+                .target_path,
+            "/applications/syncthing.app"
+        );
         assert_eq!(data.results[0].target_flags, [2, 15, 0]);
+        assert_eq!(data.results[0].target_flags_named[0], vec!["is_directory"]);
         assert_eq!(data.results[0].username, String::new());
         assert_eq!(data.results[0].folder_index, 0);
         assert_eq!(data.results[0].uid, 0);
@@ -319,6 +580,18 @@ mod tests {
         assert_eq!(data.results[0].has_executable_flag, false);
     }
 
+    #[test]
+    fn test_parse_loginitems_bytes() {
+        let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_location.push("tests/test_data/backgrounditems_sierra.btm");
+        let bytes = std::fs::read(&test_location).unwrap();
+
+        let data = LoginItemsData::parse_loginitems_bytes(&bytes).unwrap();
+        assert_eq!(data.results.len(), 1);
+        assert_eq!(data.results[0].path, ["Applications", "Syncthing.app"]);
+        assert_eq!(data.results[0].cnid_path, [103, 706090]);
+    }
+
     #[test]
     fn test_timestamps() {
         let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
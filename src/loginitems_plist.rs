@@ -2,32 +2,117 @@
 //!
 //! Provides a library to parse LoginItems data.
 
+use std::io::Read;
+
 use log::warn;
 use plist::{Dictionary, Value};
 
 /// Parse PLIST file and get Vec of bookmark data
 pub fn get_bookmarks(path: &str) -> Result<Vec<Vec<u8>>, plist::Error> {
     let login_items: Dictionary = plist::from_file(path)?;
-    for (key, value) in login_items {
-        if key != "$objects" {
-            continue;
+    Ok(get_bookmarks_from_dictionary(&login_items))
+}
+
+/// Parse PLIST data from any `Read` source (an in-memory buffer, stdin, a carved blob, ...) and
+/// get Vec of bookmark data, running the exact same decode pipeline as [`get_bookmarks`]
+pub fn get_bookmarks_from_reader<R: Read>(reader: R) -> Result<Vec<Vec<u8>>, plist::Error> {
+    let login_items: Dictionary = plist::from_reader(reader)?;
+    Ok(get_bookmarks_from_dictionary(&login_items))
+}
+
+/// Pull the bookmark `Data` blobs out of an already-parsed LoginItems PLIST dictionary
+fn get_bookmarks_from_dictionary(login_items: &Dictionary) -> Vec<Vec<u8>> {
+    let objects = match login_items.get("$objects") {
+        Some(Value::Array(objects)) => objects,
+        _ => {
+            warn!("No $objects array in LoginItems PLIST");
+            return Vec::new();
         }
-        match value {
-            Value::Array(value_array) => {
-                let results = get_array_values(value_array)?;
-                return Ok(results);
-            }
-            _ => {
-                warn!("Empty PLIST Array data");
+    };
+
+    if let Some(results) = resolve_keyed_archiver_graph(login_items, objects) {
+        if !results.is_empty() {
+            return results;
+        }
+    }
+
+    // Legacy Sierra-era files are not a full NSKeyedArchiver object graph; the bookmark Data
+    // blobs sit directly in (or one dictionary level below) the flat $objects array instead.
+    get_array_values(objects.clone())
+}
+
+/// Resolve `$top` -> item array -> per-item `CF$UID` references through `$objects` to locate the
+/// actual bookmark `NSData` payloads, preserving their original list ordering.
+fn resolve_keyed_archiver_graph(login_items: &Dictionary, objects: &[Value]) -> Option<Vec<Vec<u8>>> {
+    let top = login_items.get("$top")?.as_dictionary()?;
+    let root_uid = top.values().find_map(resolve_uid)?;
+    let root = objects.get(root_uid as usize)?;
+
+    let item_uids: Vec<u64> = match root {
+        Value::Array(items) => items.iter().filter_map(resolve_uid).collect(),
+        Value::Dictionary(dict) => match dict.get("NS.objects") {
+            Some(Value::Array(items)) => items.iter().filter_map(resolve_uid).collect(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    if item_uids.is_empty() {
+        return None;
+    }
+
+    let mut bookmarks = Vec::new();
+    for uid in item_uids {
+        let item = objects.get(uid as usize)?;
+        let mut visited = std::collections::HashSet::new();
+        if let Some(data) = find_bookmark_data(item, objects, &mut visited) {
+            bookmarks.push(data);
+        }
+    }
+    Some(bookmarks)
+}
+
+/// Follow an NSKeyedArchiver object-graph item (directly, or through another `CF$UID` pointer)
+/// until the bookmark `Data` payload it ultimately references is found. `visited` tracks the
+/// `$objects` indices already walked on this path so a `CF$UID` reference cycle in a crafted or
+/// corrupt file bails out instead of recursing forever.
+fn find_bookmark_data(
+    item: &Value,
+    objects: &[Value],
+    visited: &mut std::collections::HashSet<usize>,
+) -> Option<Vec<u8>> {
+    match item {
+        Value::Data(data) => Some(data.clone()),
+        Value::Dictionary(dict) => {
+            for value in dict.values() {
+                if let Value::Data(data) = value {
+                    return Some(data.clone());
+                }
+                if let Some(uid) = resolve_uid(value) {
+                    let index = uid as usize;
+                    if !visited.insert(index) {
+                        continue;
+                    }
+                    if let Some(resolved) = objects.get(index) {
+                        if let Some(data) = find_bookmark_data(resolved, objects, visited) {
+                            return Some(data);
+                        }
+                    }
+                }
             }
+            None
         }
+        _ => None,
     }
-    let empty_bookmark: Vec<Vec<u8>> = Vec::new();
-    Ok(empty_bookmark)
+}
+
+/// Extract the index referenced by a `{"CF$UID": N}` pointer dictionary
+fn resolve_uid(value: &Value) -> Option<u64> {
+    value.as_dictionary()?.get("CF$UID")?.as_unsigned_integer()
 }
 
 /// Loop through Array values and identify bookmark data (should be at least 48 bytes in size (header is 48 bytes))
-fn get_array_values(data_results: Vec<Value>) -> Result<Vec<Vec<u8>>, plist::Error> {
+fn get_array_values(data_results: Vec<Value>) -> Vec<Vec<u8>> {
     let mut bookmark_data: Vec<Vec<u8>> = Vec::new();
     for data in data_results {
         match data {
@@ -73,12 +158,12 @@ fn get_array_values(data_results: Vec<Value>) -> Result<Vec<Vec<u8>>, plist::Err
         }
     }
 
-    Ok(bookmark_data)
+    bookmark_data
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{get_array_values, get_bookmarks};
+    use super::{find_bookmark_data, get_array_values, get_bookmarks};
     use plist::{Dictionary, Value};
     use std::path::PathBuf;
 
@@ -106,7 +191,7 @@ mod tests {
             }
             match value {
                 Value::Array(value_array) => {
-                    results = get_array_values(value_array).unwrap();
+                    results = get_array_values(value_array);
                 }
                 _ => {
                     panic!("Unsupported Value type, expected array. Got: {:?}", value)
@@ -115,4 +200,23 @@ mod tests {
         }
         assert!(results.len() == 1);
     }
+
+    #[test]
+    fn test_find_bookmark_data_cycle_does_not_recurse_forever() {
+        let mut a = Dictionary::new();
+        a.insert("k".to_string(), uid_ref(1));
+        let mut b = Dictionary::new();
+        b.insert("k".to_string(), uid_ref(0));
+
+        let objects = vec![Value::Dictionary(a), Value::Dictionary(b)];
+        let mut visited = std::collections::HashSet::new();
+        let result = find_bookmark_data(&objects[0], &objects, &mut visited);
+        assert_eq!(result, None);
+    }
+
+    fn uid_ref(index: u64) -> Value {
+        let mut uid = Dictionary::new();
+        uid.insert("CF$UID".to_string(), Value::Integer(index.into()));
+        Value::Dictionary(uid)
+    }
 }
@@ -7,17 +7,30 @@ use crate::{
 };
 
 pub fn parse_loginitems_system() -> Result<Vec<LoginItemsResults>, LoginItemError> {
+    let mut loginitems_data: Vec<LoginItemsResults> = Vec::new();
+    parse_loginitems_system_with(|data| loginitems_data.push(data))?;
+    Ok(loginitems_data)
+}
+
+/// Same sweep as [`parse_loginitems_system`], but invokes `callback` with each result as soon
+/// as it is parsed instead of collecting them into a `Vec` first, so a caller can stream a
+/// system-wide sweep without holding every user's results in memory at once
+pub fn parse_loginitems_system_with(
+    mut callback: impl FnMut(LoginItemsResults),
+) -> Result<(), LoginItemError> {
     let base_directory = "/Users/";
     let loginitems_path =
         "/Library/Application Support/com.apple.backgroundtaskmanagementagent/backgrounditems.btm";
 
-    let mut loginitems_data: Vec<LoginItemsResults> = Vec::new();
     let dir_results = read_dir(base_directory);
     let read_dir = match dir_results {
         Ok(dir) => dir,
         Err(err) => {
             error!("Failed to read base User directory: {:?}", err);
-            return Err(LoginItemError::Path);
+            return Err(LoginItemError::Path {
+                path: base_directory.to_string(),
+                source: err,
+            });
         }
     };
 
@@ -37,19 +50,13 @@ pub fn parse_loginitems_system() -> Result<Vec<LoginItemsResults>, LoginItemErro
             let plist_path = full_path.display().to_string();
             let results = LoginItemsData::parse_loginitems(&plist_path);
             match results {
-                Ok(data) => loginitems_data.push(data),
+                Ok(data) => callback(data),
                 Err(err) => return Err(err),
             }
         }
     }
 
-    let mut app_loginitems = LoginItemsData::loginitem_apps_system()?;
-    loginitems_data.append(&mut app_loginitems);
-    if !loginitems_data.is_empty() {
-        return Ok(loginitems_data);
-    }
-
-    Ok(loginitems_data)
+    LoginItemsData::loginitem_apps_system_with(callback)
 }
 
 pub fn parse_loginitems_path(path: &str) -> Result<LoginItemsResults, LoginItemError> {
@@ -57,16 +64,44 @@ pub fn parse_loginitems_path(path: &str) -> Result<LoginItemsResults, LoginItemE
     Ok(results)
 }
 
+/// Parse LoginItems data from an in-memory buffer rather than an on-disk file
+pub fn parse_loginitems_bytes(data: &[u8]) -> Result<LoginItemsResults, LoginItemError> {
+    LoginItemsData::parse_loginitems_bytes(data)
+}
+
 pub fn parse_loginitems_bundled_path(path: &str) -> Result<Vec<LoginItemsResults>, LoginItemError> {
     LoginItemsData::loginitems_bundled_apps_path(path)
 }
 
+/// Same sweep as [`parse_loginitems_bundled_path`], but streams each result to `callback`
+/// instead of collecting into a `Vec` first
+pub fn parse_loginitems_bundled_path_with(
+    path: &str,
+    callback: impl FnMut(LoginItemsResults),
+) -> Result<(), LoginItemError> {
+    LoginItemsData::loginitems_bundled_apps_path_with(path, callback)
+}
+
+/// Sweep every user under `/Users/` and parse their `backgrounditems.btm`/SFL(2) LoginItems
+pub fn parse_loginitems_all_users() -> Result<Vec<LoginItemsResults>, LoginItemError> {
+    LoginItemsData::parse_all_users()
+}
+
+/// Same sweep as [`parse_loginitems_all_users`], but streams each result to `callback` instead
+/// of collecting into a `Vec` first
+pub fn parse_loginitems_all_users_with(
+    callback: impl FnMut(LoginItemsResults),
+) -> Result<(), LoginItemError> {
+    LoginItemsData::parse_all_users_with(callback)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use crate::parser::parse_loginitems_bundled_path;
 
+    use super::parse_loginitems_all_users;
     use super::parse_loginitems_path;
     use super::parse_loginitems_system;
 
@@ -77,6 +112,13 @@ mod tests {
         assert!(results.len() > 0);
     }
 
+    #[test]
+    #[ignore = "User accounts and LoginItems vary on a live system"]
+    fn test_parse_loginitems_all_users() {
+        let results = parse_loginitems_all_users().unwrap();
+        assert!(results.len() > 0);
+    }
+
     #[test]
     fn test_parse_loginitems_path() {
         let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -96,10 +138,12 @@ mod tests {
         assert_eq!(results.results[0].volume_size, 160851517440);
         assert_eq!(results.results[0].volume_creation, 241134516.0);
         assert_eq!(results.results[0].volume_flag, [4294967425, 4294972399, 0]);
+        assert!(results.results[0].volume_flag_named[0].contains(&"is_local".to_string()));
         assert_eq!(results.results[0].volume_root, true);
         assert_eq!(results.results[0].localized_name, "Syncthing");
-        assert_eq!(results.results[0].security_extension, "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29;00000000;00000000;0000000000000020;com.apple.app-sandbox.read-write;01;01000004;00000000000ac62a;/applications/syncthing.app\0");
+        assert_eq!(results.results[0].security_extension_rw, "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29;00000000;00000000;0000000000000020;com.apple.app-sandbox.read-write;01;01000004;00000000000ac62a;/applications/syncthing.app\0");
         assert_eq!(results.results[0].target_flags, [2, 15, 0]);
+        assert_eq!(results.results[0].target_flags_named[0], vec!["is_directory"]);
         assert_eq!(results.results[0].username, String::new());
         assert_eq!(results.results[0].folder_index, 0);
         assert_eq!(results.results[0].uid, 0);
@@ -112,6 +156,17 @@ mod tests {
         assert_eq!(results.results[0].modified_time, 1651730740);
     }
 
+    #[test]
+    fn test_parse_loginitems_bytes() {
+        let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_location.push("tests/test_data/backgrounditems_sierra.btm");
+        let bytes = std::fs::read(&test_location).unwrap();
+
+        let results = crate::parser::parse_loginitems_bytes(&bytes).unwrap();
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].path, ["Applications", "Syncthing.app"]);
+    }
+
     #[test]
     fn test_parse_loginitems_bundled_path() {
         let mut test_location = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
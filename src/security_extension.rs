@@ -0,0 +1,141 @@
+//! Decode the sandbox security-extension tokens embedded in bookmark data
+//!
+//! `security_extension_rw`/`security_extension_ro` are raw semicolon-delimited strings such as
+//! `"64cb7eaa...;00000000;...;com.apple.app-sandbox.read-write;01;01000004;...;/applications/syncthing.app\0"`.
+//! This module splits a token into its hash, sandbox extension class, flag/identifier segments,
+//! and normalized target path so investigators do not have to string-split these by hand.
+
+/// The sandbox extension class a security-extension token grants
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum SecurityExtensionClass {
+    Read,
+    ReadWrite,
+    Other(String),
+}
+
+impl From<&str> for SecurityExtensionClass {
+    fn from(value: &str) -> Self {
+        match value {
+            "com.apple.app-sandbox.read-write" => SecurityExtensionClass::ReadWrite,
+            "com.apple.app-sandbox.read" => SecurityExtensionClass::Read,
+            other => SecurityExtensionClass::Other(other.to_string()),
+        }
+    }
+}
+
+/// A decoded sandbox security-extension token
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SecurityExtensionToken {
+    pub hash: String,
+    pub class: SecurityExtensionClass,
+    /// The remaining semicolon-delimited segments (flags and trailing inode/identifier fields)
+    /// between the hash and the target path, in their original order
+    pub flags: Vec<String>,
+    pub target_path: String,
+}
+
+/// Split a raw `security_extension_rw`/`security_extension_ro` token into its structured fields.
+/// Returns `None` if `raw` is empty or does not contain a recognizable sandbox extension class.
+pub fn parse_security_extension_token(raw: &str) -> Option<SecurityExtensionToken> {
+    let trimmed = raw.trim_end_matches('\0');
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let segments: Vec<&str> = trimmed.split(';').collect();
+    if segments.len() < 3 {
+        return None;
+    }
+
+    let class_index = segments
+        .iter()
+        .position(|segment| segment.starts_with("com.apple.app-sandbox."))?;
+
+    let hash = segments[0].to_string();
+    let class = SecurityExtensionClass::from(segments[class_index]);
+    let target_path = segments.last()?.to_string();
+
+    let flags = segments[1..class_index]
+        .iter()
+        .chain(segments[class_index + 1..segments.len() - 1].iter())
+        .map(|segment| segment.to_string())
+        .collect();
+
+    Some(SecurityExtensionToken {
+        hash,
+        class,
+        flags,
+        target_path,
+    })
+}
+
+/// Route a raw bookmark `security_extension` token into the read-write or read-only slot based
+/// on the sandbox extension class it names. Tokens that don't name a recognized sandbox
+/// extension class fall into the read-only slot untouched, rather than being dropped, so
+/// callers can still inspect whatever the bookmark actually contained
+pub fn classify_raw_token(raw: &str) -> (String, String) {
+    if raw.contains("com.apple.app-sandbox.read-write") {
+        (raw.to_string(), String::new())
+    } else {
+        (String::new(), raw.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_raw_token, parse_security_extension_token, SecurityExtensionClass};
+
+    const READ_WRITE_TOKEN: &str = "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29;00000000;00000000;0000000000000020;com.apple.app-sandbox.read-write;01;01000004;00000000000ac62a;/applications/syncthing.app\0";
+    const READ_ONLY_TOKEN: &str = "46d8327f9637aa681e789f0fc10ad53b5ab5343e2ccace15d15e508c16c64fbc;00;00000000;00000000;00000000;000000000000001a;com.apple.app-sandbox.read;01;0100000a;0fffffff0004db59;02;/system/library/coreservices/system events.app\0";
+
+    #[test]
+    fn test_classify_raw_token_read_write() {
+        let (rw, ro) = classify_raw_token(READ_WRITE_TOKEN);
+        assert_eq!(rw, READ_WRITE_TOKEN);
+        assert_eq!(ro, "");
+    }
+
+    #[test]
+    fn test_classify_raw_token_read_only() {
+        let (rw, ro) = classify_raw_token(READ_ONLY_TOKEN);
+        assert_eq!(rw, "");
+        assert_eq!(ro, READ_ONLY_TOKEN);
+    }
+
+    #[test]
+    fn test_classify_raw_token_empty() {
+        assert_eq!(classify_raw_token(""), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn test_classify_raw_token_unrecognized_class_preserved() {
+        let token = "00;00000000;com.apple.app-sandbox.unknown-class;/some/path\0";
+        let (rw, ro) = classify_raw_token(token);
+        assert_eq!(rw, "");
+        assert_eq!(ro, token);
+    }
+
+    #[test]
+    fn test_parse_security_extension_token_read_write() {
+        let token = parse_security_extension_token(READ_WRITE_TOKEN).unwrap();
+        assert_eq!(token.hash, "64cb7eaa9a1bbccc4e1397c9f2a411ebe539cd29");
+        assert_eq!(token.class, SecurityExtensionClass::ReadWrite);
+        assert_eq!(token.target_path, "/applications/syncthing.app");
+        assert_eq!(token.flags, vec!["00000000", "00000000", "0000000000000020", "01", "01000004", "00000000000ac62a"]);
+    }
+
+    #[test]
+    fn test_parse_security_extension_token_read_only() {
+        let token = parse_security_extension_token(READ_ONLY_TOKEN).unwrap();
+        assert_eq!(token.class, SecurityExtensionClass::Read);
+        assert_eq!(
+            token.target_path,
+            "/system/library/coreservices/system events.app"
+        );
+    }
+
+    #[test]
+    fn test_parse_security_extension_token_empty() {
+        assert!(parse_security_extension_token("").is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Live monitoring of LoginItems persistence files
+//!
+//! Watches the per-user `backgrounditems.btm` files and `com.apple.LSSharedFileList.*.sfl2`
+//! paths that [`crate::loginitems::LoginItemsData::parse_all_users`] already enumerates,
+//! re-parsing and diffing them whenever the OS rewrites one so that persistence being
+//! installed can be detected in near-real-time.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::error;
+
+use crate::error::LoginItemError;
+use crate::loginitems::LoginItemsData;
+
+/// A single Added/Removed/Modified delta produced by [`watch_loginitems`]
+#[derive(Debug, Clone)]
+pub enum LoginItemChange {
+    Added(LoginItemsData),
+    Removed(LoginItemsData),
+    Modified {
+        old: LoginItemsData,
+        new: LoginItemsData,
+    },
+}
+
+/// How often the polling fallback re-parses and diffs LoginItems on non-macOS targets
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Identify a parsed LoginItem by path + cnid_path so entries can be matched across snapshots
+type EntryKey = (Vec<String>, Vec<i64>);
+
+fn entry_key(entry: &LoginItemsData) -> EntryKey {
+    (entry.path.clone(), entry.cnid_path.clone())
+}
+
+/// Parse every user's LoginItems right now, keyed for diffing against the previous snapshot.
+/// Built on [`LoginItemsData::parse_all_users`] rather than
+/// [`crate::parser::parse_loginitems_system`], since the latter aborts the entire sweep and
+/// returns `Err` the moment a single user's file fails to parse — which would make one
+/// unreadable or transiently mid-write file report every login item on the system as
+/// `Removed` on this cycle and `Added` again on the next.
+fn snapshot() -> HashMap<EntryKey, LoginItemsData> {
+    let mut map = HashMap::new();
+    match LoginItemsData::parse_all_users() {
+        Ok(results) => {
+            for result in results {
+                for entry in result.results {
+                    map.insert(entry_key(&entry), entry);
+                }
+            }
+        }
+        Err(err) => error!("Failed to snapshot LoginItems while watching: {:?}", err),
+    }
+    map
+}
+
+/// Compare two snapshots and produce the Added/Removed/Modified deltas between them. An entry
+/// counts as Modified when its target creation time or security extension token changed, since
+/// those are the attributes that move when a login item is re-pointed at a different binary.
+fn diff(previous: &HashMap<EntryKey, LoginItemsData>, current: &HashMap<EntryKey, LoginItemsData>) -> Vec<LoginItemChange> {
+    let mut changes = Vec::new();
+
+    for (key, entry) in current {
+        match previous.get(key) {
+            None => changes.push(LoginItemChange::Added(entry.clone())),
+            Some(old)
+                if old.creation != entry.creation
+                    || old.security_extension_rw != entry.security_extension_rw
+                    || old.security_extension_ro != entry.security_extension_ro =>
+            {
+                changes.push(LoginItemChange::Modified {
+                    old: old.clone(),
+                    new: entry.clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    for (key, entry) in previous {
+        if !current.contains_key(key) {
+            changes.push(LoginItemChange::Removed(entry.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Watch every user's LoginItems persistence files for changes, invoking `callback` once per
+/// Added/Removed/Modified entry whenever the OS rewrites one of them. Registers with the macOS
+/// FSEvents API (coalescing the duplicate create/modify events FSEvents is known to deliver for
+/// a single logical change) when available, falling back to a polling loop on other targets.
+///
+/// The initial snapshot is reported as a burst of `Added` events before watching begins.
+pub fn watch_loginitems(mut callback: impl FnMut(LoginItemChange)) -> Result<(), LoginItemError> {
+    let mut previous = snapshot();
+    for entry in previous.values() {
+        callback(LoginItemChange::Added(entry.clone()));
+    }
+
+    watch_impl(&mut previous, &mut callback)
+}
+
+#[cfg(target_os = "macos")]
+fn watch_impl(
+    previous: &mut HashMap<EntryKey, LoginItemsData>,
+    callback: &mut impl FnMut(LoginItemChange),
+) -> Result<(), LoginItemError> {
+    use std::sync::mpsc::channel;
+
+    use log::warn;
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let (tx, rx) = channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            warn!(
+                "Failed to start FSEvents watcher, falling back to polling: {:?}",
+                err
+            );
+            return watch_poll(previous, callback);
+        }
+    };
+
+    for path in watched_paths() {
+        if let Err(err) = watcher.watch(&path, RecursiveMode::NonRecursive) {
+            warn!("Failed to watch {:?}: {:?}", path, err);
+        }
+    }
+
+    loop {
+        let event = match rx.recv() {
+            Ok(Ok(event)) => event,
+            Ok(Err(err)) => {
+                warn!("FSEvents watcher error: {:?}", err);
+                continue;
+            }
+            Err(err) => {
+                error!("FSEvents watcher channel closed: {:?}", err);
+                return Ok(());
+            }
+        };
+
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            continue;
+        }
+
+        // FSEvents is known to deliver several create/modify events for one logical rewrite of
+        // a backgrounditems.btm/sfl2 file; drain whatever is already queued so the diff below
+        // only runs once per rewrite instead of once per raw event.
+        while rx.try_recv().is_ok() {}
+
+        let current = snapshot();
+        for change in diff(previous, &current) {
+            callback(change);
+        }
+        *previous = current;
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn watch_impl(
+    previous: &mut HashMap<EntryKey, LoginItemsData>,
+    callback: &mut impl FnMut(LoginItemChange),
+) -> Result<(), LoginItemError> {
+    watch_poll(previous, callback)
+}
+
+/// Polling fallback for platforms without FSEvents: re-parse and diff every [`POLL_INTERVAL`]
+fn watch_poll(
+    previous: &mut HashMap<EntryKey, LoginItemsData>,
+    callback: &mut impl FnMut(LoginItemChange),
+) -> Result<(), LoginItemError> {
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let current = snapshot();
+        for change in diff(previous, &current) {
+            callback(change);
+        }
+        *previous = current;
+    }
+}
+
+/// The set of per-user LoginItems persistence paths that currently exist on disk
+#[cfg(target_os = "macos")]
+fn watched_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    let Ok(dir) = std::fs::read_dir("/Users/") else {
+        return paths;
+    };
+
+    for entry in dir.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let home_path = entry.path().display().to_string();
+        for loginitems_path in LoginItemsData::user_loginitems_paths(&home_path) {
+            paths.push(PathBuf::from(loginitems_path));
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, entry_key, LoginItemChange};
+    use crate::loginitems::LoginItemsData;
+    use std::collections::HashMap;
+
+    fn test_entry(path: &str, creation: f64) -> LoginItemsData {
+        let mut test_location = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_location.push("tests/test_data/backgrounditems_sierra.btm");
+        let mut data = LoginItemsData::parse_loginitems(&test_location.display().to_string())
+            .unwrap()
+            .results
+            .remove(0);
+        data.path = vec![path.to_string()];
+        data.creation = creation;
+        data
+    }
+
+    #[test]
+    fn test_diff_detects_added_and_removed() {
+        let old_entry = test_entry("old.app", 1.0);
+        let mut previous = HashMap::new();
+        previous.insert(entry_key(&old_entry), old_entry.clone());
+
+        let new_entry = test_entry("new.app", 2.0);
+        let mut current = HashMap::new();
+        current.insert(entry_key(&new_entry), new_entry.clone());
+
+        let changes = diff(&previous, &current);
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .any(|change| matches!(change, LoginItemChange::Added(entry) if entry.path == new_entry.path)));
+        assert!(changes
+            .iter()
+            .any(|change| matches!(change, LoginItemChange::Removed(entry) if entry.path == old_entry.path)));
+    }
+
+    #[test]
+    fn test_diff_detects_modified() {
+        let old_entry = test_entry("syncthing.app", 1.0);
+        let mut previous = HashMap::new();
+        previous.insert(entry_key(&old_entry), old_entry.clone());
+
+        let mut new_entry = old_entry.clone();
+        new_entry.creation = 2.0;
+        let mut current = HashMap::new();
+        current.insert(entry_key(&new_entry), new_entry.clone());
+
+        let changes = diff(&previous, &current);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], LoginItemChange::Modified { old, new } if old.creation == 1.0 && new.creation == 2.0));
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let entry = test_entry("syncthing.app", 1.0);
+        let mut previous = HashMap::new();
+        previous.insert(entry_key(&entry), entry.clone());
+        let mut current = HashMap::new();
+        current.insert(entry_key(&entry), entry);
+
+        assert!(diff(&previous, &current).is_empty());
+    }
+}
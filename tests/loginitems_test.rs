@@ -23,6 +23,8 @@ fn loginitems_test() {
     let target_flags = [2, 15, 0];
 
     assert_eq!(loginitems_data.results[0].creation, creation);
+    assert_eq!(loginitems_data.results[0].creation_unix, 1643781189);
+    assert!(loginitems_data.results[0].creation_iso.starts_with("2022-02"));
     assert_eq!(loginitems_data.results[0].path, path);
     assert_eq!(loginitems_data.results[0].cnid_path, cnid);
     assert_eq!(loginitems_data.results[0].volume_path, volume_path);
@@ -32,6 +34,7 @@ fn loginitems_test() {
     assert_eq!(loginitems_data.results[0].volume_creation, volume_creation);
     assert_eq!(loginitems_data.results[0].volume_size, volume_size);
     assert_eq!(loginitems_data.results[0].volume_flag, volume_flags);
+    assert!(loginitems_data.results[0].volume_flag_named[0].contains(&"is_local".to_string()));
     assert_eq!(loginitems_data.results[0].volume_root, volume_root);
     assert_eq!(loginitems_data.results[0].localized_name, localized_name);
     assert_eq!(loginitems_data.results[0].security_extension_rw, extension);
@@ -80,10 +83,15 @@ fn loginitems_poisonapple() {
     assert_eq!(loginitems_data.results[0].security_extension_ro, "");
     assert_eq!(loginitems_data.results[0].security_extension_rw, "");
     assert_eq!(loginitems_data.results[0].target_flags, [530, 543, 538]);
+    assert!(loginitems_data.results[0].target_flags_named[0].contains(&"is_application".to_string()));
     assert_eq!(loginitems_data.results[0].username, "");
     assert_eq!(loginitems_data.results[0].folder_index, 0);
     assert_eq!(loginitems_data.results[0].uid, 0);
     assert_eq!(loginitems_data.results[0].creation_options, 536870912);
+    assert_eq!(
+        loginitems_data.results[0].creation_options_named,
+        vec!["without_implicit_security_scope"]
+    );
     assert_eq!(loginitems_data.results[0].is_bundled, false);
     assert_eq!(loginitems_data.results[0].app_binary, "");
     assert_eq!(loginitems_data.results[0].app_id, "");